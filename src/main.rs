@@ -1,26 +1,116 @@
 use circular_buffer::CircularBuffer;
 
 use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Write};
 use std::path::Path;
 use std::collections::HashMap;
 
 const WORD_FREQUENCIES_FILENAME:&str ="data/wordle_words_freqs_full.txt";
 const MAX_SCORE:usize = 3_usize.pow(5);
+const MAX_TURNS:u32 = 6;
+const TOP_GUESSES_SHOWN:usize = 10;
+const TOP_K_LOOKAHEAD:usize = 10;
 
 fn main() {
     let freq_entries = read_words();
+    let word_count = freq_entries.len();
+    let score_matrix = build_score_matrix(&freq_entries);
+    let word_index = build_word_index(&freq_entries);
     let mut remaining_candidates = build_remaining_candidates(&freq_entries);
-    filter_candidates(&to_word("tares"), to_ternary("bybyb"), &mut remaining_candidates);
-    filter_candidates(&to_word("colin"), to_ternary("ybbbb"), &mut remaining_candidates);
-    filter_candidates(&to_word("psych"), to_ternary("bbyyb"), &mut remaining_candidates);
-    for guess in remaining_candidates.keys() {
-        print!("{}\n", from_word(&guess));
+
+    if std::env::args().any(|arg| arg == "--optimal") {
+        print_optimal_opening(&freq_entries, &remaining_candidates, &score_matrix, word_count);
+        return;
+    }
+    if std::env::args().any(|arg| arg == "--benchmark") {
+        run_benchmark(&freq_entries, &remaining_candidates, &score_matrix, word_count, false);
+        run_benchmark(&freq_entries, &remaining_candidates, &score_matrix, word_count, true);
+        return;
+    }
+
+    let hard_mode = std::env::args().any(|arg| arg == "--hard");
+    let mut history: Vec<(u16, u16)> = Vec::new();
+    let mut aborted = false;
+
+    for turn in 1..=MAX_TURNS {
+        if remaining_candidates.len() <= 1 {
+            break;
+        }
+
+        print!("*** TURN {} ({} candidates remaining) ***\n", turn, remaining_candidates.len());
+        let no_history: Vec<(u16, u16)> = Vec::new();
+        let best_guesses = compute_best_guesses(&freq_entries, &remaining_candidates, &score_matrix, word_count, if hard_mode {&history} else {&no_history});
+        for (guess_idx, entropy) in best_guesses.iter().rev().take(TOP_GUESSES_SHOWN) {
+            print!("{} {}\n", entropy, from_word(&freq_entries[*guess_idx as usize].0));
+        }
+
+        let guess_idx = match read_guess(&word_index) {
+            Some(guess_idx) => guess_idx,
+            None => { aborted = true; break; }
+        };
+        let score = match read_score() {
+            Some(score) => score,
+            None => { aborted = true; break; }
+        };
+        filter_candidates(guess_idx, score, &mut remaining_candidates, &score_matrix, word_count);
+        history.push((guess_idx, score));
+    }
+
+    if aborted {
+        print!("No more input - stopping before the game finished.\n");
+        return;
     }
-    print!("*** AFTER TARES APPLIED ***\n");
-    let best_guesses = compute_best_guesses(&freq_entries, &remaining_candidates);
-    for (&guess, entropy) in best_guesses.iter() {
-        print!("{} {}\n", entropy, from_word(&guess));
+
+    match remaining_candidates.len() {
+        1 => print!("Solved! The word is {}\n", from_word(&freq_entries[*remaining_candidates.keys().next().unwrap() as usize].0)),
+        0 => print!("No candidates remain - check the guesses and patterns you entered.\n"),
+        _ => {
+            print!("*** OUT OF TURNS, {} CANDIDATES REMAIN ***\n", remaining_candidates.len());
+            for guess_idx in remaining_candidates.keys() {
+                print!("{}\n", from_word(&freq_entries[*guess_idx as usize].0));
+            }
+        }
+    }
+}
+
+// Reprompts on malformed input (wrong length, not a dictionary word); only returns `None`
+// once stdin is exhausted or can't be read, so the caller can tell "gave up mid-game" apart from
+// "played all six turns".
+fn read_guess(word_index: &HashMap<[char; 5], u16>) -> Option<u16> {
+    loop {
+        print!("Guess played (5 letters): ");
+        io::stdout().flush().expect("failed to flush stdout");
+        let line = read_trimmed_line()?;
+        if line.chars().count() != 5 {
+            print!("'{}' is not 5 letters, try again.\n", line);
+            continue;
+        }
+        match word_index.get(&to_word(&line)) {
+            Some(&idx) => return Some(idx),
+            None => print!("'{}' isn't in the dictionary, try again.\n", line),
+        }
+    }
+}
+
+fn read_score() -> Option<u16> {
+    loop {
+        print!("Result (b/y/g x5): ");
+        io::stdout().flush().expect("failed to flush stdout");
+        let line = read_trimmed_line()?;
+        if line.chars().count() != 5 || !line.chars().all(|ch| ch == 'b' || ch == 'y' || ch == 'g') {
+            print!("'{}' isn't a valid b/y/g pattern, try again.\n", line);
+            continue;
+        }
+        return Some(to_ternary(&line));
+    }
+}
+
+fn read_trimmed_line() -> Option<String> {
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) => None,
+        Ok(_) => Some(line.trim().to_lowercase()),
+        Err(_) => None,
     }
 }
 
@@ -45,32 +135,216 @@ fn read_words() -> Vec<([char; 5], f64)> {
     ret
 }
 
-fn compute_best_guesses<'a>(freq_entries: &'a Vec<([char; 5], f64)>, remaining_candidates: &HashMap<&[char; 5], f64>) -> Vec<(&'a [char; 5], f64)> {
-    let mut ret:Vec<(&[char; 5], f64)> = Vec::new();
-    for (guess, _) in freq_entries.iter() {
-        let guess_power = compute_guess_power(&guess, &remaining_candidates);
+// Precomputes `score_guess(freq_entries[i].0, freq_entries[j].0)` for every pair of word
+// indices, flattened into a single `word_count * word_count` row-major table so later turns
+// can look scores up instead of recomputing them.
+fn build_score_matrix(freq_entries: &Vec<([char; 5], f64)>) -> Vec<u16> {
+    let word_count = freq_entries.len();
+    let mut matrix = vec![0_u16; word_count * word_count];
+    for guess_idx in 0..word_count {
+        let guess = &freq_entries[guess_idx].0;
+        for candidate_idx in 0..word_count {
+            matrix[guess_idx * word_count + candidate_idx] = score_guess(guess, &freq_entries[candidate_idx].0);
+        }
+    }
+    matrix
+}
+
+fn build_word_index(freq_entries: &Vec<([char; 5], f64)>) -> HashMap<[char; 5], u16> {
+    let mut word_index = HashMap::new();
+    for (idx, (word, _freq)) in freq_entries.iter().enumerate() {
+        word_index.insert(*word, idx as u16);
+    }
+    word_index
+}
+
+fn compute_best_guesses(freq_entries: &Vec<([char; 5], f64)>, remaining_candidates: &HashMap<u16, f64>, score_matrix: &[u16], word_count: usize, history: &[(u16, u16)]) -> Vec<(u16, f64)> {
+    let mut ret:Vec<(u16, f64)> = Vec::new();
+    for guess_idx in 0..freq_entries.len() {
+        if !is_legal_under_history(guess_idx as u16, history, score_matrix, word_count) {
+            continue;
+        }
+        let guess_power = compute_guess_power(guess_idx as u16, remaining_candidates, score_matrix, word_count);
         if guess_power > 0.0 {
-            ret.push( (guess, guess_power) );
+            ret.push( (guess_idx as u16, guess_power) );
         }
     }
     ret.sort_by(|a,b| a.1.partial_cmp(&b.1).unwrap());
     ret
 }
 
-fn build_remaining_candidates(freq_entries: &Vec<([char;5], f64)>) -> HashMap<&[char; 5], f64> {
-    let mut remaining_candidates:HashMap<&[char; 5], f64> = HashMap::new();
-    for x in freq_entries.iter() {
+// Hard mode: a guess is only legal if playing it against every prior guess would have
+// reproduced that turn's observed score, i.e. the guess is itself consistent with every
+// constraint revealed so far.
+fn is_legal_under_history(guess_idx: u16, history: &[(u16, u16)], score_matrix: &[u16], word_count: usize) -> bool {
+    history.iter().all(|&(prev_guess_idx, observed_score)| {
+        score_matrix[prev_guess_idx as usize * word_count + guess_idx as usize] == observed_score
+    })
+}
+
+fn build_remaining_candidates(freq_entries: &Vec<([char;5], f64)>) -> HashMap<u16, f64> {
+    let mut remaining_candidates:HashMap<u16, f64> = HashMap::new();
+    for (idx, x) in freq_entries.iter().enumerate() {
         let likelihood = sigmoid(quadratic_curve_fit(x.1));
-        if likelihood > 0.0 { remaining_candidates.insert(&x.0, likelihood); }
+        if likelihood > 0.0 { remaining_candidates.insert(idx as u16, likelihood); }
     }
     remaining_candidates
 }
 
-fn filter_candidates(word: &[char;5], score: u16, candidates: &mut HashMap<&[char; 5], f64>) {
-    candidates.retain(|candidate, _freq| score_guess(word, candidate) == score);
+fn filter_candidates(guess_idx: u16, score: u16, candidates: &mut HashMap<u16, f64>, score_matrix: &[u16], word_count: usize) {
+    let row = &score_matrix[guess_idx as usize * word_count..(guess_idx as usize + 1) * word_count];
+    candidates.retain(|&candidate_idx, _freq| row[candidate_idx as usize] == score);
+}
+
+fn print_optimal_opening(freq_entries: &Vec<([char; 5], f64)>, remaining_candidates: &HashMap<u16, f64>, score_matrix: &[u16], word_count: usize) {
+    let mut answers: Vec<u16> = remaining_candidates.keys().copied().collect();
+    answers.sort();
+
+    let mut cache: HashMap<Vec<u16>, (u16, f64)> = HashMap::new();
+    let (best_idx, expected_guesses) = compute_optimal_guess(remaining_candidates, &answers, TOP_K_LOOKAHEAD, true, &mut cache, score_matrix, word_count);
+    print!("*** OPTIMAL OPENING GUESS (full lookahead, top-{} candidates per node) ***\n", TOP_K_LOOKAHEAD);
+    print!("{} ({} expected total guesses over {} answers)\n", from_word(&freq_entries[best_idx as usize].0), expected_guesses, answers.len());
+}
+
+// Plays the solver (using the greedy entropy guess from `compute_best_guesses`) against
+// every word in `answer_words` as the hidden solution, and reports how well it does.
+// With `hard_mode` set, each guess must itself be consistent with every score observed
+// so far in that game, mirroring Wordle's hard-mode rule.
+fn run_benchmark(freq_entries: &Vec<([char; 5], f64)>, answer_words: &HashMap<u16, f64>, score_matrix: &[u16], word_count: usize, hard_mode: bool) {
+    let solutions: Vec<u16> = answer_words.keys().copied().collect();
+    let (histogram, wins, turns_sum) = compute_benchmark_stats(freq_entries, &solutions, score_matrix, word_count, hard_mode);
+
+    print!("*** SELF-PLAY BENCHMARK ({} words{}) ***\n", solutions.len(), if hard_mode {", hard mode"} else {""});
+    print!("win rate: {:.2}%\n", 100.0 * wins as f64 / solutions.len() as f64);
+    if wins > 0 {
+        print!("mean guesses (wins only): {:.3}\n", turns_sum as f64 / wins as f64);
+    }
+    for turn in 1..=MAX_TURNS {
+        print!("  {} guesses: {}\n", turn, histogram[(turn - 1) as usize]);
+    }
+    print!("  failed: {}\n", histogram[MAX_TURNS as usize]);
+}
+
+// Plays out one game per entry in `solutions`, each starting from the full candidate set
+// built by `build_remaining_candidates`, and tallies how many turns each took. Returns the turn
+// histogram (index 0..MAX_TURNS are turn counts 1..=MAX_TURNS, the last slot is
+// failures), the win count, and the summed turn count across wins (for the mean).
+fn compute_benchmark_stats(freq_entries: &Vec<([char; 5], f64)>, solutions: &Vec<u16>, score_matrix: &[u16], word_count: usize, hard_mode: bool) -> ([u32; MAX_TURNS as usize + 1], u32, u32) {
+    let all_green_score = (MAX_SCORE - 1) as u16;
+
+    let mut histogram = [0u32; MAX_TURNS as usize + 1];
+    let mut wins = 0u32;
+    let mut turns_sum = 0u32;
+
+    for &solution_idx in solutions {
+        let mut candidates = build_remaining_candidates(freq_entries);
+        let mut history: Vec<(u16, u16)> = Vec::new();
+        let mut solved_on_turn = None;
+
+        for turn in 1..=MAX_TURNS {
+            if candidates.len() == 1 {
+                solved_on_turn = Some(turn);
+                break;
+            }
+            let no_history: Vec<(u16, u16)> = Vec::new();
+            let guess_idx = match compute_best_guesses(freq_entries, &candidates, score_matrix, word_count, if hard_mode {&history} else {&no_history}).last() {
+                Some((guess_idx, _)) => *guess_idx,
+                None => break,
+            };
+            let score = score_matrix[guess_idx as usize * word_count + solution_idx as usize];
+            filter_candidates(guess_idx, score, &mut candidates, score_matrix, word_count);
+            history.push((guess_idx, score));
+            if score == all_green_score {
+                solved_on_turn = Some(turn);
+                break;
+            }
+        }
+
+        match solved_on_turn {
+            Some(turn) => {
+                histogram[(turn - 1) as usize] += 1;
+                wins += 1;
+                turns_sum += turn;
+            }
+            None => histogram[MAX_TURNS as usize] += 1,
+        }
+    }
+
+    (histogram, wins, turns_sum)
 }
 
+// Minimizes, for the answer set `answers`, either the expected total number of guesses
+// across all answers (`expected_mode`) or the worst-case depth to solve any answer.
+// Returns the chosen guess (as a freq_entries index) and its cost, memoized on the
+// answer set so repeated sub-trees across branches are only solved once.
+fn compute_optimal_guess(
+    likelihoods: &HashMap<u16, f64>,
+    answers: &Vec<u16>,
+    top_k: usize,
+    expected_mode: bool,
+    cache: &mut HashMap<Vec<u16>, (u16, f64)>,
+    score_matrix: &[u16],
+    word_count: usize,
+) -> (u16, f64) {
+    if answers.len() == 1 {
+        // Exactly one candidate remains, so the next guess trivially solves it - that's
+        // still one guess, not zero.
+        return (answers[0], 1.0);
+    }
+    if let Some(cached) = cache.get(answers) {
+        return *cached;
+    }
+
+    let all_green_score = (MAX_SCORE - 1) as u16;
+    let mut best_guess = answers[0];
+    let mut best_cost = f64::INFINITY;
+
+    for guess_idx in top_k_guesses(likelihoods, answers, top_k, score_matrix, word_count) {
+        let row = &score_matrix[guess_idx as usize * word_count..(guess_idx as usize + 1) * word_count];
+        let mut buckets: HashMap<u16, Vec<u16>> = HashMap::new();
+        for &answer_idx in answers {
+            buckets.entry(row[answer_idx as usize]).or_insert_with(Vec::new).push(answer_idx);
+        }
 
+        let mut cost = if expected_mode {answers.len() as f64} else {1.0};
+        for (score, bucket) in buckets.iter() {
+            if *score == all_green_score && bucket.len() == 1 {
+                continue;
+            }
+            let (_, sub_cost) = compute_optimal_guess(likelihoods, bucket, top_k, expected_mode, cache, score_matrix, word_count);
+            if expected_mode {
+                cost += sub_cost;
+            } else {
+                cost = f64::max(cost, 1.0 + sub_cost);
+            }
+        }
+
+        if cost < best_cost {
+            best_cost = cost;
+            best_guess = guess_idx;
+        }
+    }
+
+    let result = (best_guess, best_cost);
+    cache.insert(answers.clone(), result);
+    result
+}
+
+// Restricted to `answers` itself (not the whole dictionary): a guess outside the
+// current answer set can never hit the all-green score against any remaining answer,
+// so the `bucket.len() == 1` base case at the call site can never fire for it and
+// `compute_optimal_guess` would recurse on the same answer set forever once such a
+// guess produced only a single bucket. Scoring within `answers` guarantees every
+// recursive call strictly shrinks its answer set.
+fn top_k_guesses(likelihoods: &HashMap<u16, f64>, answers: &Vec<u16>, top_k: usize, score_matrix: &[u16], word_count: usize) -> Vec<u16> {
+    let candidate_pool: HashMap<u16, f64> = answers.iter().map(|&idx| (idx, likelihoods[&idx])).collect();
+    let mut scored: Vec<(u16, f64)> = answers.iter()
+        .map(|&idx| (idx, compute_guess_power(idx, &candidate_pool, score_matrix, word_count)))
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    scored.truncate(top_k);
+    scored.into_iter().map(|(idx, _)| idx).collect()
+}
 
 fn compute_overall_freq<'a>(mut line: impl Iterator<Item = &'a str>) -> ([char; 5], f64) {
     let word:String = line.next().expect("there was no word here").to_string();
@@ -161,26 +435,32 @@ fn score_guess(guess: &[char;5], candidate: &[char;5]) -> u16 {
     ret
 }
 
-fn compute_guess_power(guess: &[char; 5], frequencies: &HashMap<&[char; 5], f64>) -> f64 {
-    let scores = score_against_dictionary(guess, frequencies);
+fn compute_guess_power(guess_idx: u16, frequencies: &HashMap<u16, f64>, score_matrix: &[u16], word_count: usize) -> f64 {
+    let scores = score_against_dictionary(guess_idx, frequencies, score_matrix, word_count);
     let entropy = scores_to_entropy(&scores, frequencies);
     entropy
 }
 
-fn scores_to_entropy(scores: &[i32; MAX_SCORE], frequencies: &HashMap<&[char; 5], f64>) -> f64 {
+fn scores_to_entropy(scores: &[f64; MAX_SCORE], frequencies: &HashMap<u16, f64>) -> f64 {
+    let total_likelihood: f64 = frequencies.values().sum();
     let mut entropy = 0.0;
-    for score in scores {
-        let guess_prob = *score as f64 / frequencies.len() as f64;
+    for likelihood_mass in scores {
+        let guess_prob = if total_likelihood == 0.0 {0.0} else {*likelihood_mass / total_likelihood};
         let information = if guess_prob == 0.0 {0.0} else {guess_prob * guess_prob.log2()};
         entropy -= information;
     }
     entropy
 }
-fn score_against_dictionary(guess: &[char; 5], dictionary: &HashMap<&[char; 5], f64>) -> [i32; MAX_SCORE] {
-    let mut ret = [0; MAX_SCORE];
-    for word in dictionary.keys() {
-        let word_score = score_guess(guess, word);
-        ret[word_score as usize] += 1;
+
+// Tallies the likelihood mass of `dictionary` into each of the `MAX_SCORE` buckets by
+// reading straight out of `guess_idx`'s precomputed row in `score_matrix`, instead of
+// recomputing `score_guess` for every candidate.
+fn score_against_dictionary(guess_idx: u16, dictionary: &HashMap<u16, f64>, score_matrix: &[u16], word_count: usize) -> [f64; MAX_SCORE] {
+    let row = &score_matrix[guess_idx as usize * word_count..(guess_idx as usize + 1) * word_count];
+    let mut ret = [0.0; MAX_SCORE];
+    for (&candidate_idx, likelihood) in dictionary.iter() {
+        let word_score = row[candidate_idx as usize];
+        ret[word_score as usize] += likelihood;
     }
     ret
 }
@@ -199,4 +479,127 @@ mod tests {
         assert_eq!(from_ternary(score_guess(&a, &b)), ['y', 'g', 'b', 'y', 'y']);
         assert_eq!(from_ternary(score_guess(&a, &c)), ['y', 'g', 'b', 'y', 'y']);
     }
+
+    #[test]
+    fn test_build_score_matrix_agrees_with_score_guess() {
+        // The matrix is indexed guess-major (`guess_idx * word_count + candidate_idx`),
+        // so a transposed build would still compile and would only show up as wrong
+        // scores for non-symmetric guess/candidate pairs like these.
+        let a = to_word("aaemp");
+        let b = to_word("maaph");
+        let c = to_word("mappa");
+        let freq_entries: Vec<([char; 5], f64)> = vec![(a, 0.0), (b, 0.0), (c, 0.0)];
+        let word_count = freq_entries.len();
+        let score_matrix = build_score_matrix(&freq_entries);
+
+        for guess_idx in 0..word_count {
+            for candidate_idx in 0..word_count {
+                let expected = score_guess(&freq_entries[guess_idx].0, &freq_entries[candidate_idx].0);
+                assert_eq!(score_matrix[guess_idx * word_count + candidate_idx], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_benchmark_stats_on_maximally_distinguishable_words() {
+        // Same four mutually letter-disjoint words as the optimal-guess fixture below,
+        // played greedily (no lookahead). Every guess only ever learns "was it this
+        // one?", so each game takes exactly one more turn than the last: solution 3 is
+        // found on turn 1 (it's the greedy guess itself), solution 2 on turn 2, and so
+        // on down to solution 0 on turn 4.
+        let freq_entries: Vec<([char; 5], f64)> = vec![
+            (to_word("abcde"), 0.0),
+            (to_word("fghij"), 0.0),
+            (to_word("klmno"), 0.0),
+            (to_word("pqrst"), 0.0),
+        ];
+        let word_count = freq_entries.len();
+        let score_matrix = build_score_matrix(&freq_entries);
+        let solutions: Vec<u16> = (0..word_count as u16).collect();
+
+        let (histogram, wins, turns_sum) = compute_benchmark_stats(&freq_entries, &solutions, &score_matrix, word_count, false);
+
+        assert_eq!(wins, 4);
+        assert_eq!(turns_sum, 1 + 2 + 3 + 4);
+        assert_eq!(histogram, [1, 1, 1, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_compute_guess_power_weights_by_likelihood_not_count() {
+        // Same guess, same two-way split, two different likelihood maps: one where the
+        // word landing in its own bucket is far more likely than the other, one where
+        // they're equally likely. `score_against_dictionary` sums likelihood mass per
+        // bucket, so the skewed map should read as lower entropy (less surprising) than
+        // the uniform 50/50 split, not the same - a uniform-count implementation would
+        // score both identically regardless of the likelihoods.
+        let guess = to_word("abcde");
+        let matches_first_letter = to_word("axyzw");
+        let shares_no_letters = to_word("fghij");
+        let freq_entries: Vec<([char; 5], f64)> = vec![
+            (guess, 0.0),
+            (matches_first_letter, 0.0),
+            (shares_no_letters, 0.0),
+        ];
+        let word_count = freq_entries.len();
+        let score_matrix = build_score_matrix(&freq_entries);
+
+        let skewed: HashMap<u16, f64> = [(1u16, 0.9), (2u16, 0.1)].into_iter().collect();
+        let uniform: HashMap<u16, f64> = [(1u16, 0.5), (2u16, 0.5)].into_iter().collect();
+
+        let skewed_entropy = compute_guess_power(0, &skewed, &score_matrix, word_count);
+        let uniform_entropy = compute_guess_power(0, &uniform, &score_matrix, word_count);
+
+        assert!(skewed_entropy < uniform_entropy);
+    }
+
+    #[test]
+    fn test_compute_optimal_guess_on_maximally_distinguishable_words() {
+        // Four words that share no letters at all: the only thing any guess can ever
+        // learn is "was it this one?", so the optimal strategy is to eliminate one
+        // candidate per guess - the classic n*(n+1)/2 worst case for expected mode and
+        // n for worst-case mode.
+        let freq_entries: Vec<([char; 5], f64)> = vec![
+            (to_word("abcde"), 0.0),
+            (to_word("fghij"), 0.0),
+            (to_word("klmno"), 0.0),
+            (to_word("pqrst"), 0.0),
+        ];
+        let word_count = freq_entries.len();
+        let score_matrix = build_score_matrix(&freq_entries);
+        let likelihoods: HashMap<u16, f64> = (0..word_count as u16).map(|idx| (idx, 1.0)).collect();
+        let answers: Vec<u16> = (0..word_count as u16).collect();
+
+        let mut cache = HashMap::new();
+        let (best_guess, expected_total) = compute_optimal_guess(&likelihoods, &answers, word_count, true, &mut cache, &score_matrix, word_count);
+        assert!((best_guess as usize) < word_count);
+        assert_eq!(expected_total, 10.0);
+
+        let mut cache = HashMap::new();
+        let (_, worst_case_depth) = compute_optimal_guess(&likelihoods, &answers, word_count, false, &mut cache, &score_matrix, word_count);
+        assert_eq!(worst_case_depth, 4.0);
+    }
+
+    #[test]
+    fn test_is_legal_under_history() {
+        let word_count = 3;
+        let score_matrix: Vec<u16> = vec![
+            242, 10, 0,
+            10, 242, 5,
+            0, 5, 242,
+        ];
+
+        // After guessing word 0 and observing score 10, only words whose score against
+        // word 0 reproduces that observation are legal next guesses.
+        let history = vec![(0u16, 10u16)];
+        assert!(is_legal_under_history(1, &history, &score_matrix, word_count));
+        assert!(!is_legal_under_history(2, &history, &score_matrix, word_count));
+        assert!(!is_legal_under_history(0, &history, &score_matrix, word_count));
+
+        // A second constraint only narrows the legal set further.
+        let history = vec![(0u16, 10u16), (1u16, 5u16)];
+        assert!(!is_legal_under_history(2, &history, &score_matrix, word_count));
+
+        // No history at all means every guess is legal.
+        assert!(is_legal_under_history(2, &[], &score_matrix, word_count));
+    }
 }